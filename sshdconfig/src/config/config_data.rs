@@ -1,48 +1,119 @@
 use std::collections::HashMap;
 
 use crate::sshdconfig_error::SshdConfigError;
-use crate::config::subcontainer::{KeywordType, SubContainer, UpdateKind};
-use crate::config::utils::{export_json, export_sshd_config, validate_config};
+use crate::config::diff::{is_cleared, values_equal, ConfigDiff, DiffEntry};
+use crate::config::format::FormatKind;
+use crate::config::match_context::MatchContext;
+use crate::config::source::{fold_layers, parse_layers};
+use crate::config::subcontainer::{KeywordType, SubContainer, UpdateKind, ValueSource};
+use crate::config::utils::{export_sshd_config, validate_config};
 
-/// ConfigData is the top-level object that contains all information for sshd_config
+/// ConfigData is the top-level object that contains all information for sshd_config:
+/// the global (unconditional) context plus every `Match` block, in the order
+/// they appeared in the config
 pub struct ConfigData {
-    pub config_lookup: HashMap<String, SubContainer>,
+    pub global: MatchContext,
+    pub match_contexts: Vec<MatchContext>,
     config_filepath: String,
 }
 
 impl ConfigData {
     pub fn new() -> Self {
-        let mut config_lookup = HashMap::new();
-        // TODO: import const_keywords mod & use VALID_KEYWORDS to initialize config_lookup
+        let mut global = MatchContext::new(Vec::new());
+        // TODO: import const_keywords mod & use VALID_KEYWORDS to initialize global.config_lookup
         // initialize config_filepath to default location based on the OS
         // need an empty temp file to run sshd -T with to get defaults
         let temp_filepath = "not implemented yet".to_string();
         let (is_valid, defaults) = validate_config(&temp_filepath);
         // parsing defaults here might be similar to import_sshd_config?
+        if is_valid {
+            let (defaults_global, _) = fold_layers(&parse_layers(&temp_filepath, &defaults));
+            for (keyword, mut sub_container) in defaults_global.config_lookup {
+                sub_container.is_default = true;
+                sub_container.source = ValueSource::Default;
+                global.config_lookup.insert(keyword, sub_container);
+            }
+        }
         Self {
-            config_lookup,
+            global,
+            match_contexts: Vec::new(),
             config_filepath: "not implemented yet".to_string(),
         }
     }
 
-    /// take input input and update config_lookup
-    pub fn import_sshd_config(&self, data: &String) {
+    /// take input data (the text of `self.config_filepath`) and update the
+    /// global context and `Match` contexts, following any `Include`
+    /// directives relative to `self.config_filepath`'s directory and
+    /// applying sshd's first-value-wins (or accumulate, for multi-valued
+    /// keywords) precedence across the resulting layers, independently per
+    /// context. Keywords from `self.global.config_lookup` that the file
+    /// doesn't mention (the compiled-in defaults from `new()`) are left
+    /// alone so `source` still reflects they were never explicitly set.
+    pub fn import_sshd_config(&mut self, data: &String) {
         // here we first update config_lookup from text
         // any user input makes that key no longer default
         // then export config to temp file
         // and run sshd -T
-        // we also could pass in the filepath and run sshd -T directly
-        // but then would need to go back and mark any defaults
-        // specifically called out in text file
+        let layers = parse_layers(&self.config_filepath, data);
+        let (global, match_contexts) = fold_layers(&layers);
+        for (keyword, sub_container) in global.config_lookup {
+            self.global.config_lookup.insert(keyword, sub_container);
+        }
+        self.match_contexts = match_contexts;
+    }
+
+    /// take input data in `format` (json, yaml, or toml) and merge it into
+    /// the global and `Match` contexts: keywords present in `data` override
+    /// the current value in their context, keywords absent from `data` are
+    /// left alone. Unlike `import_sshd_config`, existing `Match` contexts are
+    /// merged into rather than replaced. A keyword is no longer a default
+    /// unless `data` gives its source as `Default`.
+    pub fn import_formatted(&mut self, data: &String, format: FormatKind) -> Result<(), SshdConfigError> {
+        let (global, match_contexts) = format.formatter(true).parse(data)?;
+        for (keyword, sub_container) in global {
+            self.global.config_lookup.insert(keyword, sub_container);
+        }
+        for context in match_contexts {
+            let lookup = self.context_lookup_mut(&context.criteria);
+            for (keyword, sub_container) in context.config_lookup {
+                lookup.insert(keyword, sub_container);
+            }
+        }
+        Ok(())
     }
 
     /// take input data and update config_lookup
-    pub fn import_json(&self, data: &String) {
-        // TODO: think of better way to validate json
-        // here we first update config_lookup from json
-        // any user input makes that key no longer default
-        // then export config to temp file
-        // and run sshd -T
+    /// # TODO
+    /// think of better way to validate json
+    pub fn import_json(&mut self, data: &String) -> Result<(), SshdConfigError> {
+        self.import_formatted(data, FormatKind::Json)
+    }
+
+    /// context_lookup returns the keyword map for the context matching
+    /// `criteria` (the empty slice for the global context), if one exists
+    fn context_lookup(&self, criteria: &[(String, String)]) -> Option<&HashMap<String, SubContainer>> {
+        if criteria.is_empty() {
+            return Some(&self.global.config_lookup);
+        }
+        self.match_contexts
+            .iter()
+            .find(|ctx| ctx.criteria == criteria)
+            .map(|ctx| &ctx.config_lookup)
+    }
+
+    /// context_lookup_mut returns the keyword map for the context matching
+    /// `criteria` (the empty slice for the global context), creating a new
+    /// `Match` context if one doesn't already exist
+    fn context_lookup_mut(&mut self, criteria: &[(String, String)]) -> &mut HashMap<String, SubContainer> {
+        if criteria.is_empty() {
+            return &mut self.global.config_lookup;
+        }
+        let position = self.match_contexts.iter().position(|ctx| ctx.criteria == criteria);
+        let index = position.unwrap_or_else(|| {
+            self.match_contexts.push(MatchContext::new(criteria.to_vec()));
+            self.match_contexts.len() - 1
+        });
+        &mut self.match_contexts[index].config_lookup
     }
 
     /// apply_config will be called from set
@@ -70,46 +141,181 @@ impl ConfigData {
         false
     }
 
-    /// compare will be called from set & test
-    /// return: hashmap with subcontainer values from self and hashmap with updateKind
-    /// for any <keyword, values> that differ between self & config,
-    /// the <keyword, updateKind> is needed for set, can be ignored for test
-    fn compare(&self, config: &ConfigData) -> (Option<HashMap<String, SubContainer>>, Option<HashMap<String, UpdateKind>>) {
-        (None, None)
+    /// compare will be called from set & test. `self` is the desired
+    /// configuration and `current` is the configuration actually in effect;
+    /// the returned diff describes, per differing keyword and context (the
+    /// global config, or a specific `Match` block), the current & desired
+    /// values and the `UpdateKind` needed to reconcile them. Values are
+    /// normalized first (see `diff::values_equal`) so drift that's only
+    /// cosmetic (case, ordering, numeric formatting) isn't reported.
+    fn compare(&self, current: &ConfigData) -> ConfigDiff {
+        let mut entries = Vec::new();
+        compare_context(&[], &current.global.config_lookup, &self.global.config_lookup, &mut entries);
+
+        let mut seen_criteria: Vec<&Vec<(String, String)>> = Vec::new();
+        for context in current.match_contexts.iter().chain(self.match_contexts.iter()) {
+            if seen_criteria.contains(&&context.criteria) {
+                continue;
+            }
+            seen_criteria.push(&context.criteria);
+            let empty = HashMap::new();
+            let current_lookup = current.context_lookup(&context.criteria).unwrap_or(&empty);
+            let desired_lookup = self.context_lookup(&context.criteria).unwrap_or(&empty);
+            compare_context(&context.criteria, current_lookup, desired_lookup, &mut entries);
+        }
+
+        ConfigDiff { entries }
     }
 
     /// update will be called from set
     /// it will call add/remove/modify accordingly
     /// return: bool indicating success/failure
-    fn update(&self, config: &HashMap<String, SubContainer>, update_kind: &HashMap<String, UpdateKind>) -> bool {
-        false
+    fn update(&mut self, diff: &ConfigDiff) -> bool {
+        for entry in &diff.entries {
+            match entry.update_kind {
+                UpdateKind::Add => {
+                    if let Some(desired) = &entry.desired {
+                        self.add(&entry.keyword, desired.clone(), &entry.context);
+                    }
+                }
+                UpdateKind::Modify => {
+                    if let Some(desired) = &entry.desired {
+                        self.modify(&entry.keyword, desired.clone(), &entry.context);
+                    }
+                }
+                UpdateKind::Remove => {
+                    if let Some(current) = &entry.current {
+                        self.remove(&entry.keyword, current.clone(), &entry.context);
+                    }
+                }
+            }
+        }
+        true
     }
 
     /// modify is intended to be called from set
-    /// when a keyword that is already defined in ConfigData needs to be changed
+    /// when a keyword that is already defined in ConfigData needs to be changed.
+    /// `context` is the `Match` criteria the keyword belongs to (empty for
+    /// the global context)
     /// # Example
     /// cd = ConfigData::new();
-    /// cd.modify("Port".to_string(), KeywordType::KeywordValue("1234".to_string()))
-    fn modify(&mut self, keyword: &String, args: KeywordType) {
-
+    /// cd.modify("Port".to_string(), KeywordType::KeywordValue("1234".to_string()), &[])
+    fn modify(&mut self, keyword: &String, args: KeywordType, context: &[(String, String)]) {
+        if let Some(sub_container) = self.context_lookup_mut(context).get_mut(keyword) {
+            sub_container.values = args;
+            sub_container.is_default = false;
+        }
     }
 
     /// add is intended to be called from set
-    /// when a keyword & its args are not already defined in ConfigData and need to be added
+    /// when a keyword & its args are not already defined in ConfigData and need to be added.
+    /// `context` is the `Match` criteria the keyword belongs to (empty for
+    /// the global context)
     /// # Example
     /// cd = ConfigData::new();
-    /// cd.add("Port".to_string(), KeywordType::KeywordValue("1234".to_string()))
-    fn add(&mut self, keyword: &String, args: KeywordType) {
-
+    /// cd.add("Port".to_string(), KeywordType::KeywordValue("1234".to_string()), &[])
+    fn add(&mut self, keyword: &String, args: KeywordType, context: &[(String, String)]) {
+        let source = if context.is_empty() {
+            ValueSource::ConfigFile {
+                path: self.config_filepath.clone(),
+                line: 0,
+            }
+        } else {
+            ValueSource::MatchBlock {
+                criteria: context.to_vec(),
+                line: 0,
+            }
+        };
+        self.context_lookup_mut(context)
+            .insert(keyword.clone(), SubContainer::new(keyword.clone(), args, false, source));
     }
 
     /// remove is intended to be called from set
-    /// when a keyword & its args are already defined in ConfigData but need to be removed
+    /// when a keyword & its args are already defined in ConfigData but need to be removed.
+    /// `context` is the `Match` criteria the keyword belongs to (empty for
+    /// the global context)
     /// # Example
     /// cd = ConfigData::new();
-    /// cd.remove("Port".to_string(), KeywordType::KeywordValue("1234".to_string()))
-    fn remove(&mut self, keyword: &String, args: KeywordType) {
+    /// cd.remove("Port".to_string(), KeywordType::KeywordValue("1234".to_string()), &[])
+    fn remove(&mut self, keyword: &String, args: KeywordType, context: &[(String, String)]) {
+        let lookup = self.context_lookup_mut(context);
+        // only remove if the value being cleared still matches what's
+        // configured, so a stale diff can't delete a keyword that's since
+        // been changed to something else
+        if lookup.get(keyword).is_some_and(|sub_container| values_equal(&sub_container.values, &args)) {
+            lookup.remove(keyword);
+        }
+    }
+}
+
+/// compare_context diffs a single context (the global config, or one
+/// `Match` block) between the current and desired keyword maps, appending
+/// an entry per keyword that needs to be added, modified, or removed. A
+/// keyword is only ever removed when `desired` explicitly clears it (an
+/// empty value, see `diff::is_cleared`) -- a keyword `desired` simply
+/// doesn't mention is left alone, since desired documents are routinely
+/// partial (e.g. `{"Port": 2222}`) and omission must not be read as "delete
+/// everything else".
+fn compare_context(
+    context: &[(String, String)],
+    current: &HashMap<String, SubContainer>,
+    desired: &HashMap<String, SubContainer>,
+    entries: &mut Vec<DiffEntry>,
+) {
+    for (keyword, desired_sub) in desired {
+        // compiled-in defaults from `new()` weren't mentioned by the desired
+        // document, so they don't ask for any change
+        if desired_sub.is_default {
+            continue;
+        }
+        let current_sub = current.get(keyword);
+        if is_cleared(&desired_sub.values) {
+            if let Some(current_sub) = current_sub {
+                if !current_sub.is_default {
+                    entries.push(DiffEntry {
+                        keyword: keyword.clone(),
+                        context: context.to_vec(),
+                        current: Some(current_sub.values.clone()),
+                        desired: None,
+                        update_kind: UpdateKind::Remove,
+                    });
+                }
+            }
+            continue;
+        }
+
+        match current_sub {
+            Some(current_sub) if !values_equal(&current_sub.values, &desired_sub.values) => {
+                entries.push(DiffEntry {
+                    keyword: keyword.clone(),
+                    context: context.to_vec(),
+                    current: Some(current_sub.values.clone()),
+                    desired: Some(desired_sub.values.clone()),
+                    update_kind: if current_sub.is_default { UpdateKind::Add } else { UpdateKind::Modify },
+                });
+            }
+            Some(_) => {}
+            None => entries.push(DiffEntry {
+                keyword: keyword.clone(),
+                context: context.to_vec(),
+                current: None,
+                desired: Some(desired_sub.values.clone()),
+                update_kind: UpdateKind::Add,
+            }),
+        }
+    }
+}
 
+/// filter_keywords returns the subset of `config_lookup` named by `keywords`,
+/// or a clone of the whole map when `keywords` is `None`
+fn filter_keywords(config_lookup: &HashMap<String, SubContainer>, keywords: &Option<Vec<String>>) -> HashMap<String, SubContainer> {
+    match keywords {
+        Some(keywords) => config_lookup
+            .iter()
+            .filter(|(keyword, _)| keywords.contains(keyword))
+            .map(|(keyword, sub_container)| (keyword.clone(), sub_container.clone()))
+            .collect(),
+        None => config_lookup.clone(),
     }
 }
 
@@ -120,8 +326,8 @@ impl Default for ConfigData {
 }
 
 pub trait Invoke {
-    fn get(&self, keywords: &Option<Vec<String>>) -> Result<(), SshdConfigError>; 
-    fn set(&self, other: &ConfigData) -> Result<(), SshdConfigError>;
+    fn get(&self, keywords: &Option<Vec<String>>, verbose: bool, format: FormatKind) -> Result<(), SshdConfigError>;
+    fn set(&mut self, other: &ConfigData) -> Result<(), SshdConfigError>;
     fn test(&self, other: &ConfigData) -> Result<(), SshdConfigError>;
 }
 
@@ -129,13 +335,29 @@ impl Invoke for ConfigData {
     /// # Example
     /// cd = ConfigData::new();
     /// cd.import_sshd_config("PasswordAuthentication yes /r/n Port 1234")
-    /// cd.get()
+    /// cd.get(&None, false, FormatKind::Json)
     /// returns {"PasswordAuthentication": "yes", "Port": 1234}
-    /// cd.get(vec!["Port".to_string()])
+    /// cd.get(&Some(vec!["Port".to_string()]), false, FormatKind::Json)
     /// returns {"Port": 1234}
-    fn get(&self, keywords: &Option<Vec<String>>) -> Result<(), SshdConfigError> {
+    /// cd.get(&Some(vec!["Port".to_string()]), true, FormatKind::Yaml)
+    /// returns Port as `value: ["1234"]` (Port is multi-valued) plus its
+    /// `source`, a ConfigFile entry with the config path and line number
+    fn get(&self, keywords: &Option<Vec<String>>, verbose: bool, format: FormatKind) -> Result<(), SshdConfigError> {
         self.file_check();
-        export_json(&self.config_lookup, keywords);
+        let filtered_global = filter_keywords(&self.global.config_lookup, keywords);
+        let filtered_match_contexts: Vec<MatchContext> = self
+            .match_contexts
+            .iter()
+            .map(|context| {
+                let mut filtered = MatchContext::new(context.criteria.clone());
+                filtered.config_lookup = filter_keywords(&context.config_lookup, keywords);
+                filtered
+            })
+            .collect();
+        match format.formatter(verbose).serialize(&filtered_global, &filtered_match_contexts) {
+            Ok(output) => println!("{output}"),
+            Err(e) => println!("{{\"error\": \"{e}\"}}"),
+        }
         Ok(())
     }
     /// # Example
@@ -146,33 +368,23 @@ impl Invoke for ConfigData {
     /// cd.set(&cd2);
     /// expected outcomes: backup sshd_config if necessary, 
     /// update keyword(s) in sshd_config & restart sshd
-    fn set(&self, other: &ConfigData) -> Result<(), SshdConfigError> {
+    fn set(&mut self, other: &ConfigData) -> Result<(), SshdConfigError> {
         self.file_check();
-        let (diff, update_kind) = other.compare(self);
-        match diff {
-            Some(diff) => {
-                match update_kind {
-                    Some(update_kind) => {
-                        self.update(&diff, &update_kind);
-                        self.backup_file();
-                        // TODO: confirm if a temporary file is required to pass into SSHD -T
-                        let temp_filepath = "temp file path".to_string();
-                        export_sshd_config(&self.config_lookup, &temp_filepath);
-                        let (is_valid, _) = validate_config(&temp_filepath);
-                        // remove temp file after use 
-                        if is_valid {
-                            export_sshd_config(&self.config_lookup, &self.config_filepath);
-                            self.apply_config();
-                        }
-                    }
-                    None => {
-                        println!("failed to parse update kind");
-                    }
-                }
-            } 
-            None => {
-                println!("{{}}");
-            }
+        let diff = other.compare(self);
+        if diff.is_empty() {
+            println!("{{}}");
+            return Ok(());
+        }
+        self.update(&diff);
+        self.backup_file();
+        // TODO: confirm if a temporary file is required to pass into SSHD -T
+        let temp_filepath = "temp file path".to_string();
+        export_sshd_config(&self.global.config_lookup, &self.match_contexts, &temp_filepath);
+        let (is_valid, _) = validate_config(&temp_filepath);
+        // remove temp file after use
+        if is_valid {
+            export_sshd_config(&self.global.config_lookup, &self.match_contexts, &self.config_filepath);
+            self.apply_config();
         }
         Ok(())
     }
@@ -182,17 +394,13 @@ impl Invoke for ConfigData {
     /// cd2 = ConfigData::new();
     /// cd2.import_sshd_config("PasswordAuthentication no") // input config
     /// cd.test(&cd2);
-    /// expected return: {"PasswordAuthentication": "yes"}
+    /// expected return: {"entries":[{"keyword":"PasswordAuthentication","context":[],"current":"yes","desired":"no","update_kind":"Modify"}]}
     fn test(&self, other: &ConfigData) -> Result<(), SshdConfigError> {
         self.file_check();
-        let (diff, _) = self.compare(other);
-        match diff {
-            Some(diff) => {
-                export_json(&diff, &None);
-            } 
-            None => {
-                println!("{{}}");
-            }
+        let diff = other.compare(self);
+        match serde_json::to_string(&diff) {
+            Ok(json) => println!("{json}"),
+            Err(e) => println!("{{\"error\": \"{e}\"}}"),
         }
         Ok(())
     }
@@ -0,0 +1,122 @@
+use serde::Serialize;
+
+use crate::config::subcontainer::{KeywordType, UpdateKind};
+
+/// DiffEntry describes one keyword that differs between a desired
+/// ConfigData and the current one: its current and desired values, which
+/// kind of change is needed to reconcile them, and which context (the
+/// global config, or a specific `Match` block, identified by its criteria)
+/// it belongs to.
+#[derive(Clone, Debug, Serialize)]
+pub struct DiffEntry {
+    pub keyword: String,
+    pub context: Vec<(String, String)>,
+    pub current: Option<KeywordType>,
+    pub desired: Option<KeywordType>,
+    pub update_kind: UpdateKind,
+}
+
+/// ConfigDiff is the full structured diff between two ConfigDatas, as
+/// produced by `ConfigData::compare` and consumed by both `test` (report
+/// only) and `set` (apply via `update`)
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ConfigDiff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// is_cleared reports whether `value` is the explicit "clear this keyword"
+/// signal (an empty value or empty list) a desired document uses to ask for
+/// a keyword's removal, as opposed to simply not mentioning the keyword at
+/// all (which leaves it untouched)
+pub fn is_cleared(value: &KeywordType) -> bool {
+    match value {
+        KeywordType::KeywordValue(value) => value.is_empty(),
+        KeywordType::KeywordValues(values) => values.is_empty(),
+    }
+}
+
+/// values_equal compares two keyword values after normalizing them so the
+/// diff doesn't report spurious drift: boolean-like `yes`/`no` compare
+/// case-insensitively, numeric values (e.g. ports) compare as integers, and
+/// multi-valued keywords (e.g. `Ciphers`) compare as an order-insensitive set
+pub fn values_equal(current: &KeywordType, desired: &KeywordType) -> bool {
+    match (current, desired) {
+        (KeywordType::KeywordValue(current), KeywordType::KeywordValue(desired)) => scalar_equal(current, desired),
+        (KeywordType::KeywordValues(current), KeywordType::KeywordValues(desired)) => {
+            if current.len() != desired.len() {
+                return false;
+            }
+            let mut remaining: Vec<&String> = desired.iter().collect();
+            for value in current {
+                match remaining.iter().position(|other| scalar_equal(value, other)) {
+                    Some(index) => {
+                        remaining.remove(index);
+                    }
+                    None => return false,
+                }
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+fn scalar_equal(a: &str, b: &str) -> bool {
+    if let (Ok(a_num), Ok(b_num)) = (a.parse::<i64>(), b.parse::<i64>()) {
+        return a_num == b_num;
+    }
+    if is_boolean_like(a) && is_boolean_like(b) {
+        return a.eq_ignore_ascii_case(b);
+    }
+    a == b
+}
+
+fn is_boolean_like(value: &str) -> bool {
+    matches!(value.to_ascii_lowercase().as_str(), "yes" | "no")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boolean_values_compare_case_insensitively() {
+        let current = KeywordType::KeywordValue("YES".to_string());
+        let desired = KeywordType::KeywordValue("yes".to_string());
+        assert!(values_equal(&current, &desired));
+    }
+
+    #[test]
+    fn numeric_values_compare_as_integers() {
+        let current = KeywordType::KeywordValue("022".to_string());
+        let desired = KeywordType::KeywordValue("22".to_string());
+        assert!(values_equal(&current, &desired));
+    }
+
+    #[test]
+    fn multi_valued_keywords_compare_order_insensitively() {
+        let current = KeywordType::KeywordValues(vec!["aes128-ctr".to_string(), "aes256-ctr".to_string()]);
+        let desired = KeywordType::KeywordValues(vec!["aes256-ctr".to_string(), "aes128-ctr".to_string()]);
+        assert!(values_equal(&current, &desired));
+    }
+
+    #[test]
+    fn multi_valued_keywords_with_mismatched_multiplicity_are_not_equal() {
+        let current = KeywordType::KeywordValues(vec!["a".to_string(), "a".to_string(), "b".to_string()]);
+        let desired = KeywordType::KeywordValues(vec!["a".to_string(), "b".to_string(), "b".to_string()]);
+        assert!(!values_equal(&current, &desired));
+    }
+
+    #[test]
+    fn empty_value_is_the_clear_signal() {
+        assert!(is_cleared(&KeywordType::KeywordValue(String::new())));
+        assert!(is_cleared(&KeywordType::KeywordValues(Vec::new())));
+        assert!(!is_cleared(&KeywordType::KeywordValue("22".to_string())));
+    }
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use crate::sshdconfig_error::SshdConfigError;
+use crate::config::format::{from_exported, to_exported, ConfigFormat};
+use crate::config::match_context::MatchContext;
+use crate::config::subcontainer::SubContainer;
+
+/// JsonFormat (de)serializes the global config plus Match contexts as json
+pub struct JsonFormat {
+    include_source: bool,
+}
+
+impl JsonFormat {
+    pub fn new(include_source: bool) -> Self {
+        Self { include_source }
+    }
+}
+
+impl ConfigFormat for JsonFormat {
+    fn parse(&self, data: &str) -> Result<(HashMap<String, SubContainer>, Vec<MatchContext>), SshdConfigError> {
+        let exported = serde_json::from_str(data).map_err(|e| SshdConfigError::Format {
+            format: "json".to_string(),
+            action: "parse".to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(from_exported(exported))
+    }
+
+    fn serialize(&self, global: &HashMap<String, SubContainer>, match_contexts: &[MatchContext]) -> Result<String, SshdConfigError> {
+        let exported = to_exported(global, match_contexts, self.include_source);
+        serde_json::to_string(&exported).map_err(|e| SshdConfigError::Format {
+            format: "json".to_string(),
+            action: "serialize".to_string(),
+            message: e.to_string(),
+        })
+    }
+}
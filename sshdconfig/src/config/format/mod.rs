@@ -0,0 +1,139 @@
+mod json;
+mod toml;
+mod yaml;
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sshdconfig_error::SshdConfigError;
+use crate::config::match_context::MatchContext;
+use crate::config::subcontainer::{KeywordType, SubContainer, ValueSource};
+
+pub use json::JsonFormat;
+pub use toml::TomlFormat;
+pub use yaml::YamlFormat;
+
+/// ConfigFormat lets the in-memory config model -- the global keyword map
+/// plus every `Match` context -- round-trip through a serialization format
+/// without either side knowing about the other -- mirrors config-rs's and
+/// skytable's format modules.
+pub trait ConfigFormat {
+    fn parse(&self, data: &str) -> Result<(HashMap<String, SubContainer>, Vec<MatchContext>), SshdConfigError>;
+    fn serialize(&self, global: &HashMap<String, SubContainer>, match_contexts: &[MatchContext]) -> Result<String, SshdConfigError>;
+}
+
+/// FormatKind selects which ConfigFormat a DSC caller wants to read/write
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FormatKind {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl FormatKind {
+    /// formatter builds the concrete ConfigFormat for this kind. `include_source`
+    /// controls whether serialize() round-trips each keyword's `ValueSource`
+    /// alongside its value.
+    pub fn formatter(&self, include_source: bool) -> Box<dyn ConfigFormat> {
+        match self {
+            FormatKind::Json => Box::new(JsonFormat::new(include_source)),
+            FormatKind::Yaml => Box::new(YamlFormat::new(include_source)),
+            FormatKind::Toml => Box::new(TomlFormat::new(include_source)),
+        }
+    }
+}
+
+/// ExportedValue is the serialized shape of a keyword's value: a bare value
+/// (single string, or array for multi-valued keywords) when provenance isn't
+/// being round-tripped, or `{value, source}` when it is. `parse` accepts
+/// either shape regardless of which one `serialize` was asked to produce.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExportedValue {
+    Bare(KeywordType),
+    WithSource { value: KeywordType, source: ValueSource },
+}
+
+/// ExportedMatchContext is the serialized shape of a single `Match` block:
+/// its criteria plus the keywords scoped to it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ExportedMatchContext {
+    criteria: Vec<(String, String)>,
+    #[serde(flatten)]
+    keywords: HashMap<String, ExportedValue>,
+}
+
+/// ExportedConfig is the full wire shape of a config document: the global
+/// keywords flattened at the top level (as before this existed), plus a
+/// sibling `Match` array carrying every `Match` context. Keeping the global
+/// keywords flattened means existing consumers that only care about
+/// unconditional settings don't have to change shape.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+struct ExportedConfig {
+    #[serde(flatten)]
+    global: HashMap<String, ExportedValue>,
+    #[serde(rename = "Match", default, skip_serializing_if = "Vec::is_empty")]
+    match_contexts: Vec<ExportedMatchContext>,
+}
+
+fn export_keywords(config_lookup: &HashMap<String, SubContainer>, include_source: bool) -> HashMap<String, ExportedValue> {
+    config_lookup
+        .iter()
+        .map(|(keyword, sub_container)| {
+            let exported = if include_source {
+                ExportedValue::WithSource {
+                    value: sub_container.values.clone(),
+                    source: sub_container.source.clone(),
+                }
+            } else {
+                ExportedValue::Bare(sub_container.values.clone())
+            };
+            (keyword.clone(), exported)
+        })
+        .collect()
+}
+
+fn import_keywords(values: HashMap<String, ExportedValue>) -> HashMap<String, SubContainer> {
+    values
+        .into_iter()
+        .map(|(keyword, exported)| {
+            // a bare value (no `source` field) is a plain user/DSC-supplied
+            // value, not a compiled-in default -- any user input makes a
+            // keyword no longer default, same as import_sshd_config
+            let (value, source) = match exported {
+                ExportedValue::Bare(value) => (value, ValueSource::ConfigFile { path: String::new(), line: 0 }),
+                ExportedValue::WithSource { value, source } => (value, source),
+            };
+            let is_default = source == ValueSource::Default;
+            (keyword.clone(), SubContainer::new(keyword, value, is_default, source))
+        })
+        .collect()
+}
+
+pub(super) fn to_exported(global: &HashMap<String, SubContainer>, match_contexts: &[MatchContext], include_source: bool) -> ExportedConfig {
+    ExportedConfig {
+        global: export_keywords(global, include_source),
+        match_contexts: match_contexts
+            .iter()
+            .map(|context| ExportedMatchContext {
+                criteria: context.criteria.clone(),
+                keywords: export_keywords(&context.config_lookup, include_source),
+            })
+            .collect(),
+    }
+}
+
+pub(super) fn from_exported(exported: ExportedConfig) -> (HashMap<String, SubContainer>, Vec<MatchContext>) {
+    let global = import_keywords(exported.global);
+    let match_contexts = exported
+        .match_contexts
+        .into_iter()
+        .map(|context| {
+            let mut match_context = MatchContext::new(context.criteria);
+            match_context.config_lookup = import_keywords(context.keywords);
+            match_context
+        })
+        .collect();
+    (global, match_contexts)
+}
@@ -0,0 +1,37 @@
+use std::collections::HashMap;
+
+use crate::sshdconfig_error::SshdConfigError;
+use crate::config::format::{from_exported, to_exported, ConfigFormat};
+use crate::config::match_context::MatchContext;
+use crate::config::subcontainer::SubContainer;
+
+/// TomlFormat (de)serializes the global config plus Match contexts as toml
+pub struct TomlFormat {
+    include_source: bool,
+}
+
+impl TomlFormat {
+    pub fn new(include_source: bool) -> Self {
+        Self { include_source }
+    }
+}
+
+impl ConfigFormat for TomlFormat {
+    fn parse(&self, data: &str) -> Result<(HashMap<String, SubContainer>, Vec<MatchContext>), SshdConfigError> {
+        let exported = toml::from_str(data).map_err(|e| SshdConfigError::Format {
+            format: "toml".to_string(),
+            action: "parse".to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(from_exported(exported))
+    }
+
+    fn serialize(&self, global: &HashMap<String, SubContainer>, match_contexts: &[MatchContext]) -> Result<String, SshdConfigError> {
+        let exported = to_exported(global, match_contexts, self.include_source);
+        toml::to_string(&exported).map_err(|e| SshdConfigError::Format {
+            format: "toml".to_string(),
+            action: "serialize".to_string(),
+            message: e.to_string(),
+        })
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+
+use crate::config::subcontainer::SubContainer;
+
+/// MatchContext is the scope of either the global (unconditional) section of
+/// sshd_config, or a single `Match` block. `criteria` holds the parsed
+/// criteria/pattern pairs from the `Match` line (e.g. `[("User", "alice")]`
+/// for `Match User alice`) and is empty for the global context.
+#[derive(Clone, Debug)]
+pub struct MatchContext {
+    pub criteria: Vec<(String, String)>,
+    pub config_lookup: HashMap<String, SubContainer>,
+}
+
+impl MatchContext {
+    pub fn new(criteria: Vec<(String, String)>) -> Self {
+        Self {
+            criteria,
+            config_lookup: HashMap::new(),
+        }
+    }
+
+    /// is_global is true for the unconditional section of sshd_config
+    pub fn is_global(&self) -> bool {
+        self.criteria.is_empty()
+    }
+}
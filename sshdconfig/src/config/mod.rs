@@ -0,0 +1,7 @@
+pub mod config_data;
+pub mod diff;
+pub mod format;
+pub mod match_context;
+pub mod source;
+pub mod subcontainer;
+pub mod utils;
@@ -0,0 +1,293 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::match_context::MatchContext;
+use crate::config::subcontainer::{KeywordType, SubContainer, ValueSource};
+
+/// multi-valued keywords accumulate values across the include chain instead
+/// of the first occurrence winning outright
+const MULTI_VALUED_KEYWORDS: &[&str] = &["HostKey", "Port", "ListenAddress", "Ciphers", "MACs", "KexAlgorithms"];
+
+/// one `Keyword args` line parsed out of a config file, tagged with where it
+/// came from and which `Match` block (if any) was active when it was parsed
+#[derive(Clone, Debug)]
+struct SourceEntry {
+    keyword: String,
+    args: String,
+    file: String,
+    line: usize,
+    context: Vec<(String, String)>,
+    included: bool,
+}
+
+/// SourceLayer is every keyword entry parsed out of a single file, in the
+/// order `Include` pulled that file into the chain
+#[derive(Clone, Debug)]
+pub struct SourceLayer {
+    entries: Vec<SourceEntry>,
+}
+
+/// parse_layers takes the already-loaded text of `entry_filepath` (the main
+/// sshd_config) plus every file it (transitively) `Include`s, returning one
+/// `SourceLayer` per file in encounter order. `Include` globs are expanded
+/// relative to the including file's directory, and canonical paths already
+/// on the chain are skipped to guard against cyclic includes. A file's layer
+/// comes before the layers of the files it includes, wherever in the file the
+/// `Include` line sits.
+pub fn parse_layers(entry_filepath: &str, data: &str) -> Vec<SourceLayer> {
+    let mut layers = Vec::new();
+    let mut visited = HashSet::new();
+    if let Ok(canonical) = fs::canonicalize(entry_filepath) {
+        visited.insert(canonical);
+    }
+    parse_text(entry_filepath, data, false, &mut layers, &mut visited, Vec::new());
+    layers
+}
+
+fn parse_file(filepath: &str, layers: &mut Vec<SourceLayer>, visited: &mut HashSet<PathBuf>, parent_context: Vec<(String, String)>) {
+    let canonical = match fs::canonicalize(filepath) {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    if !visited.insert(canonical) {
+        // already included somewhere earlier in the chain
+        return;
+    }
+    let data = match fs::read_to_string(filepath) {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+    parse_text(filepath, &data, true, layers, visited, parent_context);
+}
+
+/// parse_text parses a single file's lines into a `SourceLayer`. `parent_context`
+/// is the `Match` criteria (if any) that was active at the `Include` directive
+/// that pulled this file in, so unconditional directives inside a file included
+/// from within a `Match` block stay scoped to that block instead of leaking
+/// into the global context.
+fn parse_text(
+    filepath: &str,
+    data: &str,
+    included: bool,
+    layers: &mut Vec<SourceLayer>,
+    visited: &mut HashSet<PathBuf>,
+    parent_context: Vec<(String, String)>,
+) {
+    // reserve this file's layer before following any `Include`, so the
+    // including file's layer precedes the layers of the files it pulls in
+    let layer_index = layers.len();
+    layers.push(SourceLayer { entries: Vec::new() });
+    let mut entries = Vec::new();
+    let mut current_context: Vec<(String, String)> = parent_context;
+    for (index, raw_line) in data.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, args) = match line.split_once(char::is_whitespace) {
+            Some((keyword, args)) => (keyword.to_string(), args.trim().to_string()),
+            None => continue,
+        };
+
+        if keyword.eq_ignore_ascii_case("Match") {
+            current_context = parse_match_criteria(&args);
+            continue;
+        }
+
+        if keyword.eq_ignore_ascii_case("Include") {
+            for include_path in expand_include(Path::new(filepath), &args) {
+                parse_file(&include_path, layers, visited, current_context.clone());
+            }
+            continue;
+        }
+
+        entries.push(SourceEntry {
+            keyword,
+            args,
+            file: filepath.to_string(),
+            line: index + 1,
+            context: current_context.clone(),
+            included,
+        });
+    }
+
+    layers[layer_index].entries = entries;
+}
+
+/// parse_match_criteria splits a `Match` line's arguments into its ordered
+/// criteria/pattern pairs, e.g. `"User alice Address 10.0.0.0/8"` becomes
+/// `[("User", "alice"), ("Address", "10.0.0.0/8")]`. The bare single-token
+/// form `Match all` (sshd's idiom for unconditionally ending a Match chain)
+/// has no pattern to pair with, and must not be discarded down to an empty
+/// criteria vec -- that's indistinguishable from the global context to
+/// `context_lookup`/`context_lookup_mut`, which would silently merge an
+/// `all` block's keywords into the global config.
+fn parse_match_criteria(args: &str) -> Vec<(String, String)> {
+    let tokens: Vec<&str> = args.split_whitespace().collect();
+    if tokens.len() == 1 {
+        return vec![(tokens[0].to_string(), String::new())];
+    }
+    tokens
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| (pair[0].to_string(), pair[1].to_string()))
+        .collect()
+}
+
+/// expand_include resolves an `Include` argument (a glob, or a list of
+/// space-separated globs) relative to the directory of the including file
+fn expand_include(including_file: &Path, pattern: &str) -> Vec<String> {
+    let base_dir = including_file.parent().unwrap_or_else(|| Path::new("."));
+    let mut matches = Vec::new();
+    for token in pattern.split_whitespace() {
+        let resolved = if Path::new(token).is_absolute() {
+            token.to_string()
+        } else {
+            base_dir.join(token).to_string_lossy().to_string()
+        };
+        match glob::glob(&resolved) {
+            Ok(paths) => {
+                for path in paths.flatten() {
+                    matches.push(path.to_string_lossy().to_string());
+                }
+            }
+            Err(_) => continue,
+        }
+    }
+    matches.sort();
+    matches
+}
+
+/// fold_layers applies sshd's precedence rules across an ordered list of
+/// source layers: for single-valued keywords the first occurrence in the
+/// include chain wins, while multi-valued keywords accumulate values in
+/// encounter order. Entries parsed inside a `Match` block are folded into
+/// their own `MatchContext` instead of the global one, keyed by the match
+/// block's criteria and kept in first-encountered order.
+pub fn fold_layers(layers: &[SourceLayer]) -> (MatchContext, Vec<MatchContext>) {
+    let mut global = MatchContext::new(Vec::new());
+    let mut match_contexts: Vec<MatchContext> = Vec::new();
+
+    for layer in layers {
+        for entry in &layer.entries {
+            let config_lookup = if entry.context.is_empty() {
+                &mut global.config_lookup
+            } else {
+                let position = match_contexts.iter().position(|ctx| ctx.criteria == entry.context);
+                let index = position.unwrap_or_else(|| {
+                    match_contexts.push(MatchContext::new(entry.context.clone()));
+                    match_contexts.len() - 1
+                });
+                &mut match_contexts[index].config_lookup
+            };
+            insert_entry(config_lookup, entry);
+        }
+    }
+
+    (global, match_contexts)
+}
+
+fn entry_source(entry: &SourceEntry) -> ValueSource {
+    if !entry.context.is_empty() {
+        return ValueSource::MatchBlock {
+            criteria: entry.context.clone(),
+            line: entry.line,
+        };
+    }
+    if entry.included {
+        ValueSource::Included {
+            path: entry.file.clone(),
+            line: entry.line,
+        }
+    } else {
+        ValueSource::ConfigFile {
+            path: entry.file.clone(),
+            line: entry.line,
+        }
+    }
+}
+
+fn insert_entry(config_lookup: &mut HashMap<String, SubContainer>, entry: &SourceEntry) {
+    if MULTI_VALUED_KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(&entry.keyword)) {
+        config_lookup
+            .entry(entry.keyword.clone())
+            .and_modify(|existing| {
+                if let KeywordType::KeywordValues(values) = &mut existing.values {
+                    values.push(entry.args.clone());
+                }
+            })
+            .or_insert_with(|| {
+                SubContainer::new(
+                    entry.keyword.clone(),
+                    KeywordType::KeywordValues(vec![entry.args.clone()]),
+                    false,
+                    entry_source(entry),
+                )
+            });
+    } else {
+        // first value wins: skip if this keyword was already set by an
+        // earlier layer in the include chain
+        config_lookup.entry(entry.keyword.clone()).or_insert_with(|| {
+            SubContainer::new(
+                entry.keyword.clone(),
+                KeywordType::KeywordValue(entry.args.clone()),
+                false,
+                entry_source(entry),
+            )
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_valued_keyword_first_occurrence_wins() {
+        let layers = parse_layers("sshd_config", "PasswordAuthentication yes\nPasswordAuthentication no\n");
+        let (global, _) = fold_layers(&layers);
+        let values = global.config_lookup.get("PasswordAuthentication").unwrap();
+        assert_eq!(values.values, KeywordType::KeywordValue("yes".to_string()));
+    }
+
+    #[test]
+    fn multi_valued_keyword_accumulates_in_encounter_order() {
+        let layers = parse_layers("sshd_config", "Port 22\nPort 2222\n");
+        let (global, _) = fold_layers(&layers);
+        let values = global.config_lookup.get("Port").unwrap();
+        assert_eq!(values.values, KeywordType::KeywordValues(vec!["22".to_string(), "2222".to_string()]));
+    }
+
+    #[test]
+    fn match_block_entries_fold_into_their_own_context() {
+        let layers = parse_layers("sshd_config", "Port 22\nMatch User alice\n\tPasswordAuthentication no\n");
+        let (global, match_contexts) = fold_layers(&layers);
+        assert!(global.config_lookup.contains_key("Port"));
+        assert!(!global.config_lookup.contains_key("PasswordAuthentication"));
+        assert_eq!(match_contexts.len(), 1);
+        assert_eq!(match_contexts[0].criteria, vec![("User".to_string(), "alice".to_string())]);
+        assert!(match_contexts[0].config_lookup.contains_key("PasswordAuthentication"));
+    }
+
+    #[test]
+    fn include_cycle_is_guarded_against_infinite_recursion() {
+        let dir = std::env::temp_dir().join(format!("sshdconfig_test_{}_{}", std::process::id(), line!()));
+        fs::create_dir_all(&dir).unwrap();
+        let main_path = dir.join("sshd_config");
+        let included_path = dir.join("included.conf");
+        fs::write(&main_path, format!("Include {}\n", included_path.display())).unwrap();
+        fs::write(&included_path, format!("Include {}\nPort 2222\n", main_path.display())).unwrap();
+
+        let data = fs::read_to_string(&main_path).unwrap();
+        let layers = parse_layers(main_path.to_str().unwrap(), &data);
+
+        // main file's layer plus the included file's layer -- the included
+        // file's back-reference to main is skipped, not re-parsed
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1].entries.len(), 1);
+        assert_eq!(layers[1].entries[0].keyword, "Port");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// KeywordType represents the shape a keyword's arguments take in sshd_config:
+/// a single value (e.g. `Port 22`) or a list of values for multi-valued
+/// keywords that may be repeated or comma-separated (e.g. `HostKey`, `Ciphers`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeywordType {
+    KeywordValue(String),
+    KeywordValues(Vec<String>),
+}
+
+/// UpdateKind describes how a keyword needs to change in order to move
+/// from the current configuration to the desired one.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum UpdateKind {
+    Add,
+    Modify,
+    Remove,
+}
+
+/// ValueSource records where a keyword's value came from: a compiled-in
+/// `sshd -T` default, the main config file, a file pulled in via `Include`,
+/// or a `Match` block (identified by its criteria) -- the same provenance
+/// `set`/`test` need to know which fragment to rewrite, and to tell an
+/// effective default from an explicitly configured value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ValueSource {
+    Default,
+    ConfigFile { path: String, line: usize },
+    Included { path: String, line: usize },
+    MatchBlock { criteria: Vec<(String, String)>, line: usize },
+}
+
+/// SubContainer holds the value(s) for a single keyword along with whether
+/// the value is a compiled-in default (from `sshd -T`) or was explicitly set,
+/// and the provenance of that value so edits can be written back to the
+/// fragment that actually declared the keyword.
+#[derive(Clone, Debug)]
+pub struct SubContainer {
+    pub keyword: String,
+    pub values: KeywordType,
+    pub is_default: bool,
+    pub source: ValueSource,
+}
+
+impl SubContainer {
+    pub fn new(keyword: String, values: KeywordType, is_default: bool, source: ValueSource) -> Self {
+        Self {
+            keyword,
+            values,
+            is_default,
+            source,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+
+use crate::config::match_context::MatchContext;
+use crate::config::subcontainer::{KeywordType, SubContainer, ValueSource};
+
+/// export_sshd_config writes the global config_lookup followed by each
+/// `Match` context (in order) out in sshd_config keyword/argument format,
+/// indenting keywords that belong to a `Match` stanza. Keywords sourced from
+/// an `Include`d fragment (`ValueSource::Included`) are written back to the
+/// fragment they came from instead of `filepath`, so rewriting a drop-in
+/// value doesn't clobber the main config file. `Match` blocks are always
+/// written to `filepath`, since their provenance is tracked by criteria
+/// rather than originating file.
+pub fn export_sshd_config(global: &HashMap<String, SubContainer>, match_contexts: &[MatchContext], filepath: &String) {
+    let mut files: HashMap<String, Vec<String>> = HashMap::new();
+    files.entry(filepath.clone()).or_default();
+
+    for sub_container in global.values() {
+        let destination = match &sub_container.source {
+            ValueSource::Included { path, .. } => path.clone(),
+            _ => filepath.clone(),
+        };
+        files.entry(destination).or_default().push(format_entry(sub_container, ""));
+    }
+
+    let main_lines = files.entry(filepath.clone()).or_default();
+    for context in match_contexts {
+        // a MatchContext with empty criteria would be indistinguishable
+        // from the global context on re-parse; match_contexts should never
+        // actually contain one, but skip it defensively rather than write
+        // out a bare, criteria-less "Match" line
+        if context.is_global() {
+            continue;
+        }
+        let criteria = context
+            .criteria
+            .iter()
+            .map(|(keyword, pattern)| if pattern.is_empty() { keyword.clone() } else { format!("{keyword} {pattern}") })
+            .collect::<Vec<String>>()
+            .join(" ");
+        main_lines.push(format!("Match {criteria}"));
+        for sub_container in context.config_lookup.values() {
+            main_lines.push(format_entry(sub_container, "\t"));
+        }
+    }
+
+    for (path, lines) in files {
+        let _ = fs::write(path, lines.join("\n"));
+    }
+}
+
+fn format_entry(sub_container: &SubContainer, indent: &str) -> String {
+    match &sub_container.values {
+        KeywordType::KeywordValue(value) => format!("{indent}{} {value}", sub_container.keyword),
+        // one line per value, the repeated-keyword form parse_layers accumulates
+        KeywordType::KeywordValues(values) => values
+            .iter()
+            .map(|value| format!("{indent}{} {value}", sub_container.keyword))
+            .collect::<Vec<String>>()
+            .join("\n"),
+    }
+}
+
+/// validate_config runs `sshd -T -f <filepath>` to verify the resulting
+/// config is accepted by sshd, returning whether it is valid along with
+/// the compiled effective configuration (used to discover defaults)
+pub fn validate_config(filepath: &String) -> (bool, String) {
+    match Command::new("sshd").arg("-T").arg("-f").arg(filepath).output() {
+        Ok(output) => (output.status.success(), String::from_utf8_lossy(&output.stdout).to_string()),
+        Err(_) => (false, String::new()),
+    }
+}
@@ -0,0 +1,8 @@
+mod config;
+mod sshdconfig_error;
+
+fn main() {
+    // entry point for the sshdconfig DSC resource; argument parsing and
+    // dispatch to get/set/test are handled elsewhere and are out of scope
+    // for this module
+}
@@ -0,0 +1,28 @@
+use thiserror::Error;
+
+/// SshdConfigError represents the possible failure modes when parsing,
+/// validating, or applying sshd_config.
+#[derive(Error, Debug)]
+pub enum SshdConfigError {
+    #[error("failed to read file '{0}': {1}")]
+    FileRead(String, String),
+
+    #[error("failed to write file '{0}': {1}")]
+    FileWrite(String, String),
+
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+
+    #[error("failed to invoke sshd -T: {0}")]
+    Validation(String),
+
+    #[error("failed to parse json: {0}")]
+    InvalidJson(String),
+
+    #[error("failed to {action} '{format}': {message}")]
+    Format {
+        format: String,
+        action: String,
+        message: String,
+    },
+}